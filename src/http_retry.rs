@@ -0,0 +1,122 @@
+use std::env;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const INITIAL_BACKOFF_MS: u64 = 500;
+const MAX_BACKOFF_MS: u64 = 8000;
+
+fn max_attempts() -> u32 {
+    env::var("HTTP_MAX_RETRIES")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_MAX_ATTEMPTS)
+}
+
+fn is_retryable_status(status_code: i32) -> bool {
+    matches!(status_code, 429 | 500 | 502 | 503 | 504)
+}
+
+/// Implemented by the minreq response types that carry a status code up front, so
+/// `send_with_retry` works the same whether the body is buffered eagerly (`Response`) or
+/// read lazily as it arrives (`ResponseLazy`).
+pub trait HasStatusCode {
+    fn status_code(&self) -> i32;
+}
+
+impl HasStatusCode for minreq::Response {
+    fn status_code(&self) -> i32 {
+        self.status_code
+    }
+}
+
+impl HasStatusCode for minreq::ResponseLazy {
+    fn status_code(&self) -> i32 {
+        self.status_code
+    }
+}
+
+/// Sends an HTTP request built by `build_and_send`, retrying with exponential backoff and
+/// jitter on network errors and on 429/500/502/503/504 responses. Other 4xx/2xx/3xx responses
+/// are returned immediately. `build_and_send` is called once per attempt so it should build a
+/// fresh `minreq::Request` each time rather than reusing one that was already consumed.
+pub fn send_with_retry<F, R>(mut build_and_send: F) -> Result<R, minreq::Error>
+where
+    F: FnMut() -> Result<R, minreq::Error>,
+    R: HasStatusCode,
+{
+    let attempts = max_attempts();
+    let mut backoff_ms = INITIAL_BACKOFF_MS;
+
+    for attempt in 1..=attempts {
+        let result = build_and_send();
+        let should_retry = match &result {
+            Ok(response) => is_retryable_status(response.status_code()),
+            Err(_) => true,
+        };
+
+        if !should_retry || attempt == attempts {
+            return result;
+        }
+
+        thread::sleep(Duration::from_millis(backoff_ms + jitter_ms(backoff_ms)));
+        backoff_ms = next_backoff_ms(backoff_ms);
+    }
+
+    unreachable!("loop always returns on its last iteration")
+}
+
+/// Doubles the previous backoff, capped at `MAX_BACKOFF_MS`.
+fn next_backoff_ms(current_ms: u64) -> u64 {
+    (current_ms * 2).min(MAX_BACKOFF_MS)
+}
+
+/// A random delay in `0..=max_ms`, derived from the clock since the crate otherwise has no
+/// dependency on a `rand` crate.
+fn jitter_ms(max_ms: u64) -> u64 {
+    if max_ms == 0 {
+        return 0;
+    }
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos() as u64;
+    nanos % (max_ms + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retryable_statuses_are_5xx_or_429() {
+        for status in [429, 500, 502, 503, 504] {
+            assert!(is_retryable_status(status), "{status} should be retryable");
+        }
+    }
+
+    #[test]
+    fn non_retryable_statuses_pass_through() {
+        for status in [200, 201, 301, 400, 401, 403, 404] {
+            assert!(!is_retryable_status(status), "{status} should not be retryable");
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_until_it_hits_the_cap() {
+        assert_eq!(next_backoff_ms(INITIAL_BACKOFF_MS), 1000);
+        assert_eq!(next_backoff_ms(1000), 2000);
+        assert_eq!(next_backoff_ms(2000), 4000);
+        assert_eq!(next_backoff_ms(4000), MAX_BACKOFF_MS);
+        assert_eq!(next_backoff_ms(MAX_BACKOFF_MS), MAX_BACKOFF_MS);
+    }
+
+    #[test]
+    fn jitter_is_bounded_by_max_and_zero_stays_zero() {
+        assert_eq!(jitter_ms(0), 0);
+        for _ in 0..20 {
+            assert!(jitter_ms(1000) <= 1000);
+        }
+    }
+}
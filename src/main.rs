@@ -1,7 +1,8 @@
+mod http_retry;
 mod ollama;
 mod subtitle;
 
-use crate::subtitle::get_video_data;
+use crate::subtitle::{get_video_data, resolve_and_get_video_data};
 use crossbeam_channel::{bounded, Receiver, Sender};
 use serde::{Deserialize, Serialize};
 use std::env;
@@ -27,6 +28,10 @@ struct SummarizeRequest {
     dry_run: bool,
     #[serde(default)]
     transcript_only: bool,
+    #[serde(default)]
+    chaptered: bool,
+    #[serde(default)]
+    translate: bool,
 }
 
 #[derive(Serialize, Clone)]
@@ -134,9 +139,15 @@ fn handle_request(stream: &mut TcpStream) -> io::Result<()> {
     let mut lines = request_data.split(|&b| b == b'\n').filter(|l| !l.is_empty());
     let request_line = lines.next().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Empty request"))?;
 
+    // Match the exact path (ignoring any query string), not just a prefix: "POST /api/submit"
+    // is a prefix of "POST /api/submit_script", and "POST /api/summarize" is a prefix of
+    // "POST /api/summarize_stream", so a starts_with chain would swallow the longer routes.
+    let path = request_line.split(|&b| b == b' ').nth(1).unwrap_or(b"/");
+    let route = path.split(|&b| b == b'?').next().unwrap_or(path);
+
     if request_line.starts_with(b"GET ") {
         handle_get(request_line, stream)
-    } else if request_line.starts_with(b"POST /api/summarize") {
+    } else if request_line.starts_with(b"POST ") && route == b"/api/summarize" {
         let content_length = get_content_length(request_data)
             .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Content-Length header is required for POST"))?;
 
@@ -149,6 +160,14 @@ fn handle_request(stream: &mut TcpStream) -> io::Result<()> {
         let req: SummarizeRequest = serde_json::from_slice(&body)
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("JSON deserialization error: {}", e)))?;
 
+        if subtitle::extract_playlist_id(&req.url).is_some() {
+            return write_error_response(
+                stream,
+                "400 Bad Request",
+                "Playlist URLs can occupy a worker for a long time; submit them via POST /api/submit instead",
+            );
+        }
+
         let response_payload = perform_summary_work(req)
             .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("Processing error: {}", e)))?;
 
@@ -156,7 +175,7 @@ fn handle_request(stream: &mut TcpStream) -> io::Result<()> {
             .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("JSON serialization error: {}", e)))?;
 
         write_response(stream, "200 OK", "application/json", response_body.as_bytes())
-    } else if request_line.starts_with(b"POST /api/submit") {
+    } else if request_line.starts_with(b"POST ") && route == b"/api/submit" {
         // background job submission
         let content_length = get_content_length(request_data)
             .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Content-Length header is required for POST"))?;
@@ -183,7 +202,7 @@ fn handle_request(stream: &mut TcpStream) -> io::Result<()> {
 
     let body = serde_json::json!({"job_id": job_id}).to_string();
         write_response(stream, "200 OK", "application/json", body.as_bytes())
-    } else if request_line.starts_with(b"POST /api/submit_script") {
+    } else if request_line.starts_with(b"POST ") && route == b"/api/submit_script" {
         // background job to generate a YouTube script from an existing summary + transcript
         let content_length = get_content_length(request_data)
             .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Content-Length header is required for POST"))?;
@@ -208,6 +227,24 @@ fn handle_request(stream: &mut TcpStream) -> io::Result<()> {
 
         let body = serde_json::json!({"job_id": job_id}).to_string();
         write_response(stream, "200 OK", "application/json", body.as_bytes())
+    } else if request_line.starts_with(b"POST ") && route == b"/api/summarize_stream" {
+        // synchronous, but streams the summary back as server-sent events as tokens arrive
+        let content_length = get_content_length(request_data)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Content-Length header is required for POST"))?;
+        if content_length > MAX_BODY_SIZE { return Err(io::Error::new(io::ErrorKind::InvalidData, "Request body too large")); }
+        let body = read_body(initial_body, content_length, stream)?;
+        let req: SummarizeRequest = serde_json::from_slice(&body)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("JSON deserialization error: {}", e)))?;
+
+        if subtitle::extract_playlist_id(&req.url).is_some() {
+            return write_error_response(
+                stream,
+                "400 Bad Request",
+                "Playlist URLs can occupy a worker for a long time; submit them via POST /api/submit instead",
+            );
+        }
+
+        perform_summary_stream_work(req, stream)
     } else {
         write_error_response(stream, "404 Not Found", "Not Found")
     }
@@ -247,32 +284,8 @@ fn handle_get(request_line: &[u8], stream: &mut TcpStream) -> io::Result<()> {
     }
 }
 
-fn perform_summary_work(req: SummarizeRequest) -> Result<SummarizeResponse, String> {
-    if req.dry_run {
-        let test_md = include_str!("./markdown_test.md").to_string();
-        return Ok(SummarizeResponse {
-            summary: test_md.clone(),
-            subtitles: test_md,
-            video_name: "Dry Run".into(),
-        });
-    }
-
-    let (transcript, video_name) = get_video_data(&req.url, "en")
-        .map_err(|e| format!("Transcript error: {}", e))?;
-
-    if req.transcript_only {
-        return Ok(SummarizeResponse {
-            summary: transcript.clone(),
-            subtitles: transcript,
-            video_name,
-        });
-    }
-
-    let model = req
-        .model
-        .filter(|m| !m.trim().is_empty())
-        .unwrap_or_else(|| "gpt-oss:20b".to_string());
-    let system_prompt = req.system_prompt.unwrap_or_else(|| r####"You are an expert video summarizer. Given a raw YouTube transcript (and optionally the video title), produce a debate-ready Markdown summary that captures the speaker's core thesis, structure, and evidence without adding facts that aren't in the transcript.
+fn default_summary_system_prompt() -> String {
+    r####"You are an expert video summarizer. Given a raw YouTube transcript (and optionally the video title), produce a debate-ready Markdown summary that captures the speaker's core thesis, structure, and evidence without adding facts that aren't in the transcript.
 
 Tone and perspective:
 - Use a neutral narrator voice: refer to the narrator as "the speaker" (e.g., "The speaker argues...").
@@ -305,7 +318,76 @@ Safety/accuracy:
 - If the transcript is incomplete or ambiguous, note "Not mentioned," "Unclear," or "Ambiguous" where appropriate.
 - Do not invent references, links, or sources.
 - Do not give prescriptive financial, medical, or legal advice; only summarize what the speaker says."####
-          .to_string());
+        .to_string()
+}
+
+fn default_chaptered_system_prompt() -> String {
+    r####"You are an expert video summarizer. The transcript below is interleaved with `[mm:ss]` markers showing roughly where each stretch of speech occurs in the video. Given this time-referenced transcript (and optionally the video title), produce a chaptered, time-referenced Markdown outline without adding facts that aren't in the transcript.
+
+Tone and perspective:
+- Use a neutral narrator voice: refer to the narrator as "the speaker" (e.g., "The speaker argues...").
+- Preserve the speaker's stance and rhetoric, but do not editorialize or inject new claims.
+- If something is not mentioned, say "Not mentioned" instead of guessing.
+
+Output format (Markdown only):
+1) Start with a punchy H2 title that captures the thesis.
+    - Format: "## {Concise, compelling title reflecting the main claim}"
+2) One short opening paragraph (2–3 sentences) that frames the overall argument.
+3) A chaptered outline: one H3 section per chapter of the video, in chronological order.
+    - Format each heading as: "### [mm:ss] {Concise, descriptive chapter title}", using the timestamp of the marker closest to where that chapter begins.
+    - For each chapter: 1–2 concise paragraphs summarizing what's covered, followed by bullet points for the key claims, bolding crucial terms.
+4) If risks, caveats, timelines, metrics, or quotes appear, preserve them verbatim (use inline quotes for short lines, blockquotes for longer).
+5) End cleanly without a generic conclusion if it repeats content.
+
+Style constraints:
+- Do not use tables. Use headings, paragraphs, and bullet lists only.
+- Keep factual fidelity: do not add numbers, timelines, or names that aren't in the transcript.
+- Every timestamp you output must come from one of the `[mm:ss]` markers in the transcript; never invent one.
+- Remove ads/sponsors, filler, repeated phrases, and irrelevant tangents.
+
+Safety/accuracy:
+- If the transcript is incomplete or ambiguous, note "Not mentioned," "Unclear," or "Ambiguous" where appropriate.
+- Do not invent references, links, or sources.
+- Do not give prescriptive financial, medical, or legal advice; only summarize what the speaker says."####
+        .to_string()
+}
+
+fn perform_summary_work(req: SummarizeRequest) -> Result<SummarizeResponse, String> {
+    if req.dry_run {
+        let test_md = include_str!("./markdown_test.md").to_string();
+        return Ok(SummarizeResponse {
+            summary: test_md.clone(),
+            subtitles: test_md,
+            video_name: "Dry Run".into(),
+        });
+    }
+
+    if let Some(playlist_id) = subtitle::extract_playlist_id(&req.url) {
+        return perform_playlist_summary_work(req, &playlist_id);
+    }
+
+    let (transcript, video_name) = resolve_and_get_video_data(&req.url, "en", req.chaptered, req.translate)
+        .map_err(|e| format!("Transcript error: {}", e))?;
+
+    if req.transcript_only {
+        return Ok(SummarizeResponse {
+            summary: transcript.clone(),
+            subtitles: transcript,
+            video_name,
+        });
+    }
+
+    let model = req
+        .model
+        .filter(|m| !m.trim().is_empty())
+        .unwrap_or_else(|| "gpt-oss:20b".to_string());
+    let system_prompt = req.system_prompt.unwrap_or_else(|| {
+        if req.chaptered {
+            default_chaptered_system_prompt()
+        } else {
+            default_summary_system_prompt()
+        }
+    });
 
     let base_url = env::var("OLLAMA_BASE_URL").unwrap_or_else(|_| "http://127.0.0.1:11434".to_string());
 
@@ -320,6 +402,172 @@ Safety/accuracy:
     })
 }
 
+/// Same pipeline as `perform_summary_work`, but streams the summary to `stream` as
+/// server-sent events as each token arrives, instead of buffering the full response.
+fn perform_summary_stream_work(req: SummarizeRequest, stream: &mut TcpStream) -> io::Result<()> {
+    write_sse_headers(stream)?;
+
+    if req.dry_run {
+        let test_md = include_str!("./markdown_test.md").to_string();
+        write_sse_event(stream, "delta", &serde_json::json!({ "content": test_md }))?;
+        return write_sse_event(stream, "done", &serde_json::json!({ "video_name": "Dry Run" }));
+    }
+
+    // Playlist URLs are rejected by the /api/summarize_stream handler before this is called,
+    // since a worker-pool thread shouldn't be tied up running the whole-playlist pipeline.
+
+    let (transcript, video_name) = match resolve_and_get_video_data(&req.url, "en", req.chaptered, req.translate) {
+        Ok(data) => data,
+        Err(e) => {
+            return write_sse_event(
+                stream,
+                "error",
+                &serde_json::json!({ "error": format!("Transcript error: {}", e) }),
+            );
+        }
+    };
+
+    if req.transcript_only {
+        write_sse_event(stream, "delta", &serde_json::json!({ "content": transcript }))?;
+        return write_sse_event(stream, "done", &serde_json::json!({ "video_name": video_name }));
+    }
+
+    let model = req
+        .model
+        .filter(|m| !m.trim().is_empty())
+        .unwrap_or_else(|| "gpt-oss:20b".to_string());
+    let system_prompt = req.system_prompt.clone().unwrap_or_else(|| {
+        if req.chaptered {
+            default_chaptered_system_prompt()
+        } else {
+            default_summary_system_prompt()
+        }
+    });
+    let base_url = env::var("OLLAMA_BASE_URL").unwrap_or_else(|_| "http://127.0.0.1:11434".to_string());
+    let user_content = format!("Title: {}\n\nTranscript:\n{}", video_name, transcript);
+
+    let summary_result = ollama::summarize_streaming(&base_url, &model, &system_prompt, &user_content, |delta| {
+        let _ = write_sse_event(stream, "delta", &serde_json::json!({ "content": delta }));
+    });
+
+    match summary_result {
+        Ok(_) => write_sse_event(stream, "done", &serde_json::json!({ "video_name": video_name })),
+        Err(e) => write_sse_event(
+            stream,
+            "error",
+            &serde_json::json!({ "error": format!("Ollama error: {}", e) }),
+        ),
+    }
+}
+
+fn write_sse_headers(stream: &mut TcpStream) -> io::Result<()> {
+    stream.write_all(
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+    )
+}
+
+fn write_sse_event(stream: &mut TcpStream, event: &str, data: &serde_json::Value) -> io::Result<()> {
+    write!(stream, "event: {}\ndata: {}\n\n", event, data)?;
+    stream.flush()
+}
+
+/// Runs the transcript+summarize pipeline over every video in a playlist and stitches the
+/// results into a single document, with an optional final "meta-summary" turn that synthesizes
+/// themes across the whole playlist.
+fn perform_playlist_summary_work(req: SummarizeRequest, playlist_id: &str) -> Result<SummarizeResponse, String> {
+    let video_ids = subtitle::get_playlist_video_ids(playlist_id).map_err(|e| format!("Playlist error: {}", e))?;
+
+    let model = req
+        .model
+        .filter(|m| !m.trim().is_empty())
+        .unwrap_or_else(|| "gpt-oss:20b".to_string());
+    let system_prompt = req.system_prompt.clone().unwrap_or_else(|| {
+        if req.chaptered {
+            default_chaptered_system_prompt()
+        } else {
+            default_summary_system_prompt()
+        }
+    });
+    let base_url = env::var("OLLAMA_BASE_URL").unwrap_or_else(|_| "http://127.0.0.1:11434".to_string());
+
+    let mut combined_transcript = String::new();
+    let mut combined_summary = String::new();
+    let mut per_video_summaries: Vec<(String, String)> = Vec::new();
+
+    for video_id in &video_ids {
+        let video_url = format!("https://www.youtube.com/watch?v={}", video_id);
+        let (transcript, video_name) = match get_video_data(&video_url, "en", req.chaptered, req.translate) {
+            Ok(data) => data,
+            Err(e) => {
+                eprintln!("⚠️ Skipping playlist video {}: {}", video_id, e);
+                continue;
+            }
+        };
+
+        combined_transcript.push_str(&format!("## {}\n\n{}\n\n", video_name, transcript));
+
+        if req.transcript_only {
+            continue;
+        }
+
+        let user_content = format!("Title: {}\n\nTranscript:\n{}", video_name, transcript);
+        match ollama::summarize(&base_url, &model, &system_prompt, &user_content) {
+            Ok(summary) => {
+                combined_summary.push_str(&format!("## {}\n\n{}\n\n", video_name, summary));
+                per_video_summaries.push((video_name, summary));
+            }
+            Err(e) => eprintln!("⚠️ Summarization failed for playlist video {}: {}", video_id, e),
+        }
+    }
+
+    let video_name = format!("Playlist ({} video(s))", video_ids.len());
+
+    if req.transcript_only {
+        return Ok(SummarizeResponse {
+            summary: combined_transcript.clone(),
+            subtitles: combined_transcript,
+            video_name,
+        });
+    }
+
+    if per_video_summaries.is_empty() {
+        return Err("No videos in the playlist could be summarized.".to_string());
+    }
+
+    if per_video_summaries.len() > 1 {
+        match build_playlist_meta_summary(&base_url, &model, &per_video_summaries) {
+            Ok(meta_summary) => {
+                combined_summary.push_str("## Across the Playlist\n\n");
+                combined_summary.push_str(&meta_summary);
+                combined_summary.push('\n');
+            }
+            Err(e) => eprintln!("⚠️ Playlist meta-summary failed: {}", e),
+        }
+    }
+
+    Ok(SummarizeResponse {
+        summary: combined_summary,
+        subtitles: combined_transcript,
+        video_name,
+    })
+}
+
+fn build_playlist_meta_summary(
+    base_url: &str,
+    model: &str,
+    per_video_summaries: &[(String, String)],
+) -> Result<String, String> {
+    let system_prompt = "You are an expert at synthesizing themes across a set of per-video summaries drawn from the same YouTube playlist. Identify the recurring themes, how the videos build on or diverge from each other, and any notable contrasts. Do not invent facts that aren't present in the summaries. Output Markdown only: a short intro paragraph followed by 3-6 bullet points on cross-video themes.";
+
+    let combined = per_video_summaries
+        .iter()
+        .map(|(title, summary)| format!("### {}\n{}", title, summary))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    ollama::summarize(base_url, model, system_prompt, &combined).map_err(|e| format!("Ollama error: {}", e))
+}
+
 fn read_headers_from_stream(stream: &mut TcpStream) -> io::Result<(Vec<u8>, usize)> {
     let mut buffer = Vec::with_capacity(1024);
     let mut chunk = [0; 256];
@@ -1,3 +1,4 @@
+use crate::http_retry;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::env;
@@ -7,6 +8,7 @@ pub enum Error {
     Request(minreq::Error),
     StatusNotOk(String),
     NoTextInResponse,
+    StreamDecode(String),
 }
 
 impl std::fmt::Display for Error {
@@ -38,6 +40,15 @@ struct ChatMessage {
     content: String,
 }
 
+#[derive(Deserialize)]
+struct StreamChatChunk {
+    message: Option<ChatMessage>,
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    done_reason: Option<String>,
+}
+
 pub fn summarize(base_url: &str, model: &str, system_prompt: &str, transcript: &str) -> Result<String, Error> {
     // Use Ollama chat API with a system + user content. If output is truncated due to length,
     // automatically issue continuation turns and concatenate results.
@@ -80,14 +91,12 @@ pub fn summarize(base_url: &str, model: &str, system_prompt: &str, transcript: &
             "stream": false
         });
 
-        let mut req = minreq::post(&url).with_header("Content-Type", "application/json");
-        if let Some(secs) = timeout_opt { req = req.with_timeout(secs); }
-
-        let response = req
-            .with_json(&body)
-            .map_err(Error::Request)?
-            .send()
-            .map_err(Error::Request)?;
+        let response = http_retry::send_with_retry(|| {
+            let mut req = minreq::post(&url).with_header("Content-Type", "application/json");
+            if let Some(secs) = timeout_opt { req = req.with_timeout(secs); }
+            req.with_json(&body)?.send()
+        })
+        .map_err(Error::Request)?;
 
         if response.status_code < 200 || response.status_code > 299 {
             let text = response.as_str().unwrap_or("").to_string();
@@ -143,3 +152,216 @@ pub fn summarize(base_url: &str, model: &str, system_prompt: &str, transcript: &
 
     Ok(accumulated)
 }
+
+/// Like `summarize`, but sets `"stream": true` and invokes `on_delta` with each incremental
+/// `message.content` chunk as it arrives over the newline-delimited JSON stream, instead of
+/// blocking until the full generation completes. Returns the full concatenated text, same as
+/// `summarize`, and keeps the same length-based auto-continuation behavior.
+pub fn summarize_streaming<F>(
+    base_url: &str,
+    model: &str,
+    system_prompt: &str,
+    transcript: &str,
+    mut on_delta: F,
+) -> Result<String, Error>
+where
+    F: FnMut(&str),
+{
+    let url = format!("{}/api/chat", base_url.trim_end_matches('/'));
+
+    let num_predict: i64 = env::var("OLLAMA_NUM_PREDICT").ok().and_then(|s| s.parse().ok()).unwrap_or(1200);
+    let num_ctx: i64 = env::var("OLLAMA_NUM_CTX").ok().and_then(|s| s.parse().ok()).unwrap_or(8192);
+    let temperature: f64 = env::var("OLLAMA_TEMPERATURE").ok().and_then(|s| s.parse().ok()).unwrap_or(0.2);
+    let repeat_penalty: f64 = env::var("OLLAMA_REPEAT_PENALTY").ok().and_then(|s| s.parse().ok()).unwrap_or(1.1);
+    let max_cont: u32 = env::var("OLLAMA_AUTO_CONT_MAX").ok().and_then(|s| s.parse().ok()).unwrap_or(2);
+
+    let timeout_opt: Option<u64> = env::var("OLLAMA_TIMEOUT_SECS")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .filter(|&v| v > 0);
+
+    let mut messages = vec![
+        Message { role: "system", content: system_prompt.to_string() },
+        Message { role: "user", content: transcript.to_string() },
+    ];
+
+    let mut accumulated = String::new();
+    let mut turns = 0u32;
+    loop {
+        turns += 1;
+        let body = json!({
+            "model": model,
+            "messages": messages,
+            "options": {
+                "temperature": temperature,
+                "repeat_penalty": repeat_penalty,
+                "num_ctx": num_ctx,
+                "num_predict": num_predict
+            },
+            "stream": true
+        });
+
+        let response = http_retry::send_with_retry(|| {
+            let mut req = minreq::post(&url).with_header("Content-Type", "application/json");
+            if let Some(secs) = timeout_opt { req = req.with_timeout(secs); }
+            req.with_json(&body)?.send_lazy()
+        })
+        .map_err(Error::Request)?;
+
+        if response.status_code < 200 || response.status_code > 299 {
+            let status_code = response.status_code;
+            let text = drain_lazy_response_to_string(response);
+            // Special-case common error: model not found. Try to suggest installed models.
+            if text.contains("not found") || status_code == 404 {
+                let tags_url = format!("{}/api/tags", base_url.trim_end_matches('/'));
+                if let Ok(tags_resp) = minreq::get(tags_url).with_timeout(5).send() {
+                    if tags_resp.status_code >= 200 && tags_resp.status_code <= 299 {
+                        if let Ok(v) = tags_resp.json::<serde_json::Value>() {
+                            let names: Vec<String> = v
+                                .get("models")
+                                .and_then(|m| m.as_array())
+                                .map(|arr| {
+                                    arr.iter()
+                                        .filter_map(|item| item.get("name").and_then(|n| n.as_str()).map(|s| s.to_string()))
+                                        .collect::<Vec<_>>()
+                                })
+                                .unwrap_or_default();
+                            let suggestion = if names.is_empty() {
+                                String::from("No local models found. Pull one, e.g.: ollama pull llama3:8b")
+                            } else {
+                                format!("Installed models: {}", names.join(", "))
+                            };
+                            let friendly = format!(
+                                "Model '{model}' not found. Pull it with: ollama pull {model}. {suggestion}",
+                                model = model,
+                                suggestion = suggestion
+                            );
+                            return Err(Error::StatusNotOk(friendly));
+                        }
+                    }
+                }
+            }
+            return Err(Error::StatusNotOk(text));
+        }
+
+        let mut turn_text = String::new();
+        let mut turn_done_reason: Option<String> = None;
+        let mut line_buf: Vec<u8> = Vec::new();
+
+        for item in response {
+            let (byte, _remaining) = item.map_err(Error::Request)?;
+            if byte != b'\n' {
+                line_buf.push(byte);
+                continue;
+            }
+            consume_stream_line(&line_buf, &mut on_delta, &mut turn_text, &mut turn_done_reason)?;
+            line_buf.clear();
+        }
+        if !line_buf.is_empty() {
+            consume_stream_line(&line_buf, &mut on_delta, &mut turn_text, &mut turn_done_reason)?;
+        }
+
+        accumulated.push_str(&turn_text);
+
+        let truncated = turn_done_reason.as_deref() == Some("length");
+        if !truncated || turns > max_cont { break; }
+
+        messages.push(Message { role: "assistant", content: turn_text });
+        messages.push(Message { role: "user", content: "Continue. Finish any unfinished sections, bullets, and examples. Maintain the same formatting.".to_string() });
+    }
+
+    Ok(accumulated)
+}
+
+/// Drains a non-2xx `ResponseLazy` body into a `String` for error reporting.
+fn drain_lazy_response_to_string(response: minreq::ResponseLazy) -> String {
+    let mut bytes = Vec::new();
+    for item in response {
+        match item {
+            Ok((byte, _remaining)) => bytes.push(byte),
+            Err(_) => break,
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Parses one line of the streamed `/api/chat` response, forwarding its content delta (if any)
+/// to `on_delta` and accumulating it into `turn_text`. Records the final chunk's `done_reason`.
+fn consume_stream_line<F: FnMut(&str)>(
+    line: &[u8],
+    on_delta: &mut F,
+    turn_text: &mut String,
+    turn_done_reason: &mut Option<String>,
+) -> Result<(), Error> {
+    if line.iter().all(|b| b.is_ascii_whitespace()) {
+        return Ok(());
+    }
+
+    let chunk: StreamChatChunk = serde_json::from_slice(line)
+        .map_err(|e| Error::StreamDecode(format!("{}: {}", e, String::from_utf8_lossy(line))))?;
+
+    if let Some(content) = chunk.message.map(|m| m.content).filter(|s| !s.is_empty()) {
+        on_delta(&content);
+        turn_text.push_str(&content);
+    }
+
+    if chunk.done {
+        *turn_done_reason = chunk.done_reason;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn consume(line: &str) -> (Result<(), Error>, String, Vec<String>, Option<String>) {
+        let mut turn_text = String::new();
+        let mut turn_done_reason = None;
+        let mut deltas = Vec::new();
+        let mut on_delta = |s: &str| deltas.push(s.to_string());
+        let result = consume_stream_line(line.as_bytes(), &mut on_delta, &mut turn_text, &mut turn_done_reason);
+        (result, turn_text, deltas, turn_done_reason)
+    }
+
+    #[test]
+    fn consume_stream_line_forwards_a_normal_delta() {
+        let (result, turn_text, deltas, done_reason) =
+            consume(r#"{"message":{"role":"assistant","content":"hello"}}"#);
+        assert!(result.is_ok());
+        assert_eq!(turn_text, "hello");
+        assert_eq!(deltas, vec!["hello".to_string()]);
+        assert_eq!(done_reason, None);
+    }
+
+    #[test]
+    fn consume_stream_line_ignores_blank_lines() {
+        let (result, turn_text, deltas, done_reason) = consume("   ");
+        assert!(result.is_ok());
+        assert_eq!(turn_text, "");
+        assert!(deltas.is_empty());
+        assert_eq!(done_reason, None);
+    }
+
+    #[test]
+    fn consume_stream_line_records_done_reason_when_done() {
+        let (result, _turn_text, _deltas, done_reason) =
+            consume(r#"{"message":null,"done":true,"done_reason":"stop"}"#);
+        assert!(result.is_ok());
+        assert_eq!(done_reason, Some("stop".to_string()));
+    }
+
+    #[test]
+    fn consume_stream_line_done_without_done_reason_is_none() {
+        let (result, _turn_text, _deltas, done_reason) = consume(r#"{"message":null,"done":true}"#);
+        assert!(result.is_ok());
+        assert_eq!(done_reason, None);
+    }
+
+    #[test]
+    fn consume_stream_line_errors_on_malformed_json() {
+        let (result, _turn_text, _deltas, _done_reason) = consume("not json");
+        assert!(matches!(result, Err(Error::StreamDecode(_))));
+    }
+}